@@ -2,17 +2,69 @@
 
 use std::borrow::Cow;
 use std::collections::HashMap;
-use std::io::{self, Write};
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Read, Write};
 use std::ops::Range;
 
 use uuid::UUID;
 
 use crate::{scan_for_float, scan_for_integer, scan_for_string, Frame};
 
+/// Errors produced while indexing or reducing a [`Batch`].
+#[derive(Debug)]
+pub enum BatchError {
+    /// A frame referred to an object whose type UUID doesn't match the
+    /// type UUID already on record for that object.
+    TypeMismatch {
+        object: UUID,
+        expected: UUID,
+        found: UUID,
+    },
+    /// A frame's type UUID doesn't match any known CRDT.
+    UnknownType(UUID),
+    /// Writing reduced output failed.
+    Io(io::Error),
+}
+
+impl fmt::Display for BatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BatchError::TypeMismatch {
+                object,
+                expected,
+                found,
+            } => write!(
+                f,
+                "mismatched type/object pair: {} vs. {} for object {}",
+                expected, found, object
+            ),
+            BatchError::UnknownType(ty) => write!(f, "unknown type {}", ty),
+            BatchError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for BatchError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            BatchError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for BatchError {
+    fn from(e: io::Error) -> BatchError {
+        BatchError::Io(e)
+    }
+}
+
 /// An iterator over frames.
 #[derive(Clone, Debug)]
 pub struct Batch<'a> {
     body: Cow<'a, str>,
+    cursor: usize,
     next: Option<Range<usize>>,
 }
 
@@ -32,12 +84,16 @@ impl<'a> Batch<'a> {
             }
         }
 
-        Batch { body: b, next: n }
+        Batch {
+            body: b,
+            cursor: 0,
+            next: n,
+        }
     }
 
     /// Indexes all frames. Returns map from object UUID to a pair of type and frames refering to
     /// the object.
-    pub fn index(self) -> Option<HashMap<UUID, (UUID, Vec<Frame<'a>>)>> {
+    pub fn index(self) -> Result<HashMap<UUID, (UUID, Vec<Frame<'a>>)>, BatchError> {
         use crate::Op;
 
         let mut index = HashMap::<UUID, (UUID, Vec<Frame<'a>>)>::default();
@@ -56,36 +112,53 @@ impl<'a> Batch<'a> {
                             "miss matched type/object pair: {} vs. {} for object {}",
                             ent.0, ty, object
                         );
-                        return None;
+                        return Err(BatchError::TypeMismatch {
+                            object,
+                            expected: ent.0.clone(),
+                            found: ty,
+                        });
                     }
                 }
                 None => {}
             }
         }
 
-        Some(index)
+        Ok(index)
     }
 
     /// Reduces all frames found in `self` and outputs the final status frames.
-    pub fn reduce_all<W>(self, mut out: W) -> io::Result<()>
+    pub fn reduce_all<W>(self, mut out: W) -> Result<(), BatchError>
     where
         W: Write,
     {
-        use crate::{Set, CRDT, LWW};
-        use std::io::{Error, ErrorKind};
+        use crate::{Set, CRDT, LWW, RGA};
         use std::str::FromStr;
 
-        let index = self
-            .index()
-            .ok_or(Error::new(ErrorKind::Other, "indexing failed"))?;
+        let index = self.index()?;
         let lww = UUID::from_str("lww").unwrap();
         let set = UUID::from_str("set").unwrap();
+        let rga = UUID::from_str("rga").unwrap();
+
+        // Reduce into an in-memory buffer first and only write it to `out`
+        // once every object has been dealt with. `index` is a HashMap, so
+        // its iteration order isn't deterministic; writing straight to
+        // `out` would leave it holding a partial, run-varying prefix of
+        // the batch if a later object couldn't be reduced.
+        let mut buf = Vec::<u8>::new();
+        // An unrecognized CRDT type is forward-compatible, not a causal
+        // inconsistency: an older replica is expected to see newer kinds
+        // in a shared log. Pass such objects through unreduced and keep
+        // going, remembering one of the offending types to report once
+        // the rest of the batch has made it to `out`. An object with a
+        // single frame needs no reducing: it is written as it is, whatever
+        // its type, and is not reported.
+        let mut unknown: Option<UUID> = None;
 
         for (_, (ty, mut frames)) in index {
             match frames.len() {
                 0 => {}
                 1 => {
-                    out.write_all(frames[0].body().as_bytes())?;
+                    buf.write_all(frames[0].body().as_bytes())?;
                 }
                 _ => {
                     let s = frames.pop().unwrap();
@@ -93,19 +166,23 @@ impl<'a> Batch<'a> {
                         LWW::reduce(s, frames)
                     } else if ty == set {
                         Set::reduce(s, frames)
+                    } else if ty == rga {
+                        RGA::reduce(s, frames)
                     } else {
                         warn!("unknown type {}", ty);
 
-                        out.write_all(s.body().as_bytes())?;
+                        unknown.get_or_insert_with(|| ty.clone());
+
+                        buf.write_all(s.body().as_bytes())?;
                         for frm in frames {
-                            out.write_all(frm.body().as_bytes())?;
+                            buf.write_all(frm.body().as_bytes())?;
                         }
                         continue;
                     };
 
                     match state {
                         Some(state) => {
-                            out.write_all(state.body().as_bytes())?;
+                            buf.write_all(state.body().as_bytes())?;
                         }
                         None => {}
                     }
@@ -113,7 +190,12 @@ impl<'a> Batch<'a> {
             }
         }
 
-        Ok(())
+        out.write_all(&buf)?;
+
+        match unknown {
+            Some(ty) => Err(BatchError::UnknownType(ty)),
+            None => Ok(()),
+        }
     }
 
     fn scan(s: &str) -> Option<Range<usize>> {
@@ -252,15 +334,18 @@ impl<'a> Iterator for Batch<'a> {
     type Item = Frame<'a>;
 
     fn next(&mut self) -> Option<Frame<'a>> {
-        if self.body.is_empty() || self.body.starts_with(".") {
+        let rest = &self.body[self.cursor..];
+
+        if rest.is_empty() || rest.starts_with(".") {
             return None;
         }
 
         let p = self.next.take();
-        let end = p.clone().map(|x| x.start).unwrap_or(self.body.len());
-        let ret = match &mut self.body {
-            &mut Cow::Borrowed(s) => Frame::parse(&s[..end]),
-            &mut Cow::Owned(ref mut s) => Frame::parse(s[..end].to_string()),
+        let end = p.clone().map(|x| x.start).unwrap_or_else(|| rest.len());
+        let cursor = self.cursor;
+        let ret = match &self.body {
+            Cow::Borrowed(s) => Frame::parse(&s[cursor..cursor + end]),
+            Cow::Owned(s) => Frame::parse(s[cursor..cursor + end].to_string()),
         };
 
         match p {
@@ -268,25 +353,15 @@ impl<'a> Iterator for Batch<'a> {
                 let start = rgn.start;
                 let end = rgn.end;
 
-                self.next = Batch::scan(&self.body[end..]).map(|x| {
+                self.next = Batch::scan(&self.body[cursor + end..]).map(|x| {
                     let l = end - start;
                     (x.start + l)..(x.end + l)
                 });
 
-                match &mut self.body {
-                    b @ &mut Cow::Borrowed(_) => {
-                        let s = match b {
-                            &mut Cow::Borrowed(s) => &s[start..],
-                            _ => unreachable!(),
-                        };
-
-                        *b = Cow::Borrowed(s);
-                    }
-                    &mut Cow::Owned(ref mut s) => s.replace_range(0..start, ""),
-                }
+                self.cursor += start;
             }
             None => {
-                self.body = Cow::Owned(String::default());
+                self.cursor = self.body.len();
             }
         }
 
@@ -294,6 +369,109 @@ impl<'a> Iterator for Batch<'a> {
     }
 }
 
+/// Incrementally parses a batch of frames out of a streaming source, such
+/// as a socket or a growing log file, where the full text isn't available
+/// up front.
+///
+/// [`feed`] reuses `Batch`'s own frame scanner to detect whole frames as
+/// they complete and yields each one as soon as it does, holding on to
+/// any trailing partial frame until more input arrives.
+///
+/// [`feed`]: Reader::feed
+#[derive(Clone, Debug, Default)]
+pub struct Reader {
+    buf: String,
+}
+
+impl Reader {
+    /// Creates an empty reader.
+    pub fn new() -> Reader {
+        Reader::default()
+    }
+
+    /// Feeds more input into the reader and returns every frame that
+    /// completed as a result, in order. A trailing partial frame, if any,
+    /// is retained for the next call.
+    pub fn feed(&mut self, s: &str) -> Vec<Frame<'static>> {
+        self.buf.push_str(s);
+
+        let mut out = Vec::default();
+        let mut cursor = 0;
+
+        while cursor < self.buf.len() {
+            let rest = &self.buf[cursor..];
+
+            if rest.starts_with(".") {
+                cursor = self.buf.len();
+                break;
+            }
+
+            match Batch::scan(rest) {
+                Some(rgn) => {
+                    out.push(Frame::parse(rest[..rgn.end].to_string()));
+                    cursor += rgn.end;
+                }
+                // Scan ran off the end of the buffer without completing a
+                // frame; wait for more input.
+                None => break,
+            }
+        }
+
+        self.buf.replace_range(0..cursor, "");
+
+        out
+    }
+
+    /// Reads `r` to completion, feeding it through [`feed`] as bytes
+    /// arrive, and returns every frame found.
+    ///
+    /// [`feed`]: Reader::feed
+    pub fn from_reader<R: Read>(mut r: R) -> io::Result<Vec<Frame<'static>>> {
+        let mut reader = Reader::new();
+        let mut out = Vec::default();
+        let mut chunk = [0u8; 4096];
+        // Bytes read but not yet decoded, because they end in a UTF-8
+        // sequence that was cut off by a chunk boundary.
+        let mut pending = Vec::<u8>::new();
+
+        loop {
+            let n = r.read(&mut chunk)?;
+
+            if n == 0 {
+                if !pending.is_empty() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "incomplete UTF-8 sequence at end of stream",
+                    ));
+                }
+
+                break;
+            }
+
+            pending.extend_from_slice(&chunk[..n]);
+
+            let valid_to = match std::str::from_utf8(&pending) {
+                Ok(_) => pending.len(),
+                Err(e) => match e.error_len() {
+                    // A genuinely malformed byte, as opposed to a
+                    // multi-byte character truncated at the end of this
+                    // chunk.
+                    Some(_) => {
+                        return Err(io::Error::new(io::ErrorKind::InvalidData, e));
+                    }
+                    None => e.valid_up_to(),
+                },
+            };
+
+            let s = std::str::from_utf8(&pending[..valid_to]).unwrap();
+            out.extend(reader.feed(s));
+            pending.drain(0..valid_to);
+        }
+
+        Ok(out)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -326,6 +504,24 @@ mod tests {
         assert_eq!(b1.count(), 3);
     }
 
+    #[test]
+    fn batch_parse_owned_matches_borrowed() {
+        let text = "*lww#test@0:0! @1:key'value' @2:number=1 *rga#text@3:0'T'! *rga#text@6:3, @4'e' @5'x' @6't' *lww#more:a=1;.";
+
+        // A `String` input takes the `Cow::Owned` path through the
+        // iterator, as opposed to the `Cow::Borrowed` path a `&str`
+        // takes; both must yield the same frames.
+        let borrowed: Vec<String> = Batch::parse(text)
+            .map(|frm| frm.body().to_string())
+            .collect();
+        let owned: Vec<String> = Batch::parse(text.to_string())
+            .map(|frm| frm.body().to_string())
+            .collect();
+
+        assert_eq!(owned, borrowed);
+        assert_eq!(owned.len(), 3);
+    }
+
     #[test]
     fn batch_reduce_all() {
         use std::io::Cursor;
@@ -344,6 +540,60 @@ mod tests {
         println!("{}", str::from_utf8(&s).unwrap());
     }
 
+    #[test]
+    fn reduce_all_passes_through_unknown_type_without_losing_other_objects() {
+        use std::io::Cursor;
+        use std::str;
+
+        let b = Batch::parse("*lww#a@0:0! *foo#test@1:0! *foo#test@2:0!");
+        let mut c = Cursor::new(Vec::default());
+
+        match b.reduce_all(&mut c) {
+            Err(BatchError::UnknownType(ty)) => {
+                assert_eq!(ty, UUID::from_str("foo").unwrap());
+            }
+            other => panic!("expected UnknownType, got {:?}", other),
+        }
+
+        // An unrecognized type is forward-compatible, not fatal: the
+        // recognized object's reduced output must still make it to `out`,
+        // and the unrecognized object's raw frames are passed through
+        // rather than discarded.
+        let out = c.into_inner();
+        let out = str::from_utf8(&out).unwrap();
+
+        assert!(out.contains("*lww#a@0:0!"));
+        assert!(out.contains("*foo#test@1:0!"));
+        assert!(out.contains("*foo#test@2:0!"));
+    }
+
+    /// A `Read` that yields one byte per call, to force every multi-byte
+    /// UTF-8 character in the stream across a chunk boundary.
+    struct OneByteAtATime<'a>(&'a [u8]);
+
+    impl<'a> std::io::Read for OneByteAtATime<'a> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.0.is_empty() {
+                return Ok(0);
+            }
+
+            buf[0] = self.0[0];
+            self.0 = &self.0[1..];
+
+            Ok(1)
+        }
+    }
+
+    #[test]
+    fn reader_from_reader_handles_utf8_split_across_chunks() {
+        let text = "*rga#text@1:0'é'!";
+
+        let frames = Reader::from_reader(OneByteAtATime(text.as_bytes())).unwrap();
+
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].body(), text);
+    }
+
     #[test]
     fn index_one_obj() {
         let b1 = Batch::parse(
@@ -380,7 +630,7 @@ mod tests {
             "*lww#test@0:0! @1:key'value' *rga#test@2:0! @3:number=1",
         );
 
-        assert!(b1.index().is_none());
+        assert!(b1.index().is_err());
     }
 
     #[test]
@@ -0,0 +1,214 @@
+//! Replicated Growable Array (RGA) CRDT.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::str::FromStr;
+
+use uuid::UUID;
+
+use crate::{CRDT, Frame, Op, Value};
+
+/// Replicated Growable Array reducer.
+///
+/// Every insertion op carries its own event UUID (the logical timestamp of
+/// the insert) and a reference UUID naming the element it was inserted
+/// after; the root reference is `0`. Reducing linearizes the tree of
+/// references into a single sequence: at each node, its children (the ops
+/// anchored on it) are visited in descending event order, so concurrent
+/// inserts at the same anchor get a deterministic causal order. Ops that
+/// tombstone an existing event drop it from the output while leaving it
+/// available as an anchor for later inserts. An insert whose reference
+/// hasn't arrived yet is unreachable from the root and is appended, in
+/// event order, after the root-reachable sequence, so a later reduce that
+/// finally sees the anchor can still find it.
+pub struct RGA;
+
+impl<'a> CRDT<'a> for RGA {
+    fn reduce(state: Frame<'a>, frames: Vec<Frame<'a>>) -> Option<Frame<'a>> {
+        let root = UUID::from_str("0").unwrap();
+
+        let mut ops = HashMap::<UUID, Op>::default();
+        let mut children = HashMap::<UUID, Vec<UUID>>::default();
+        let mut tombstones = HashMap::<UUID, ()>::default();
+        let mut header: Option<(UUID, UUID)> = None;
+
+        for frm in std::iter::once(state).chain(frames.into_iter()) {
+            if header.is_none() {
+                if let Some(op) = frm.peek() {
+                    header = Some((op.ty.clone(), op.object.clone()));
+                }
+            }
+
+            for op in frm {
+                match op.value {
+                    // An insert: remember it and register it as a child of
+                    // the element it was anchored on. A reference that
+                    // hasn't been seen yet is still recorded here rather
+                    // than dropped; it simply won't be reachable from the
+                    // walk below until its anchor shows up. The same op
+                    // can legitimately show up in both the state frame and
+                    // an update frame, so only its first sighting counts.
+                    Some(_) => {
+                        if ops.contains_key(&op.event) {
+                            continue;
+                        }
+
+                        children
+                            .entry(op.location.clone())
+                            .or_insert_with(Vec::default)
+                            .push(op.event.clone());
+                        ops.insert(op.event.clone(), op);
+                    }
+                    // A tombstone: it names an existing event and carries
+                    // no atom of its own.
+                    None => {
+                        tombstones.insert(op.event.clone(), ());
+                    }
+                }
+            }
+        }
+
+        for siblings in children.values_mut() {
+            siblings.sort_by(|a, b| b.cmp(a));
+        }
+
+        let (ty, object) = header?;
+        let mut body = format!("*{}#{}", ty, object);
+        let mut visited = HashSet::<UUID>::default();
+
+        // Iterative pre-order walk starting at the root reference; an
+        // explicit stack keeps this from overflowing on long documents.
+        let mut stack: Vec<UUID> = children
+            .get(&root)
+            .cloned()
+            .unwrap_or_default()
+            .into_iter()
+            .rev()
+            .collect();
+
+        while let Some(event) = stack.pop() {
+            // A malicious or buggy peer could replicate a cyclic
+            // `location` graph that runs through the root event (e.g.
+            // `@1:0` and `@0:1`, or `@0:0`); without this guard the walk
+            // would bounce between the same events forever instead of
+            // just risking a deep stack. Cycles not anchored on the root
+            // are never reached here and end up in `unreached` below.
+            if !visited.insert(event.clone()) {
+                continue;
+            }
+
+            let op = match ops.get(&event) {
+                Some(op) => op,
+                None => continue,
+            };
+
+            if !tombstones.contains_key(&event) {
+                write!(body, " @{}:{}", op.event, op.location).ok()?;
+                RGA::write_atom(&mut body, &op.value)?;
+            }
+
+            if let Some(kids) = children.get(&event) {
+                stack.extend(kids.iter().rev().cloned());
+            }
+        }
+
+        // Anything not reachable from the root is still real data that a
+        // future reduce might be able to place once its anchor shows up;
+        // keep it in the output, in a stable (event-ascending) order.
+        let mut unreached: Vec<UUID> = ops
+            .keys()
+            .filter(|event| !visited.contains(*event))
+            .cloned()
+            .collect();
+        unreached.sort();
+
+        for event in unreached {
+            if tombstones.contains_key(&event) {
+                continue;
+            }
+
+            let op = &ops[&event];
+            write!(body, " @{}:{}", op.event, op.location).ok()?;
+            RGA::write_atom(&mut body, &op.value)?;
+        }
+
+        body.push('!');
+
+        Some(Frame::parse(body))
+    }
+}
+
+impl RGA {
+    fn write_atom(body: &mut String, value: &Option<Value>) -> Option<()> {
+        match value {
+            Some(Value::Str(s)) => write!(body, "'{}'", s).ok(),
+            Some(Value::Int(i)) => write!(body, "={}", i).ok(),
+            Some(Value::Float(f)) => write!(body, "^{}", f).ok(),
+            Some(Value::Uuid(u)) => write!(body, ">{}", u).ok(),
+            None => Some(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reduce_linearizes_a_chain_of_inserts() {
+        let state = Frame::parse("*rga#text@3:0'T'!");
+        let updates = vec![Frame::parse("*rga#text@4:3'e'!")];
+
+        let out = RGA::reduce(state, updates).unwrap();
+
+        assert_eq!(out.body(), "*rga#text @3:0'T' @4:3'e'!");
+    }
+
+    #[test]
+    fn reduce_orders_concurrent_siblings_by_descending_event() {
+        let state = Frame::parse("*rga#text@3:0'T'!");
+        let updates = vec![
+            Frame::parse("*rga#text@4:3'a'!"),
+            Frame::parse("*rga#text@5:3'b'!"),
+        ];
+
+        let out = RGA::reduce(state, updates).unwrap();
+
+        assert_eq!(out.body(), "*rga#text @3:0'T' @5:3'b' @4:3'a'!");
+    }
+
+    #[test]
+    fn reduce_retains_unreachable_inserts() {
+        // An insert anchored on an event that hasn't replicated yet must
+        // be retained in the output instead of dropped, so a later
+        // reduce that finally sees the anchor can still find it.
+        let state = Frame::parse("*rga#text@9:5'z'!");
+
+        let out = RGA::reduce(state, Vec::new()).unwrap();
+
+        assert_eq!(out.body(), "*rga#text @9:5'z'!");
+    }
+
+    #[test]
+    fn reduce_terminates_on_self_referencing_insert() {
+        // A peer could replicate a pathological insert whose event and
+        // location coincide (`@0:0`), which would otherwise make the
+        // root-reachable walk push the same event back onto the stack
+        // forever instead of terminating.
+        let state = Frame::parse("*rga#text@0:0'x'!");
+
+        let out = RGA::reduce(state, Vec::new()).unwrap();
+
+        assert_eq!(out.body(), "*rga#text @0:0'x'!");
+    }
+
+    #[test]
+    fn reduce_deduplicates_ops_seen_in_state_and_updates() {
+        let state = Frame::parse("*rga#text@3:0'T'! *rga#text@4:3'e'!");
+        let updates = vec![Frame::parse("*rga#text@4:3'e'!")];
+
+        let out = RGA::reduce(state, updates).unwrap();
+
+        assert_eq!(out.body(), "*rga#text @3:0'T' @4:3'e'!");
+    }
+}